@@ -1,5 +1,106 @@
 use std::fmt;
+use std::borrow::Cow;
 use super::attribute::{Variant, Accent};
+use super::renderer::MathmlRenderer;
+
+/// Escape the XML metacharacters `&`, `<`, `>`, and `"` in user-supplied text
+/// so that it cannot break out of the surrounding MathML markup.
+///
+/// Strings that contain none of these characters are returned unchanged
+/// without allocating.
+pub(crate) fn escape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"']) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Unicode glyph pairs for named stretchy-delimiter commands, kept here as
+/// the single source of truth that the parser draws on when it turns
+/// `\lvert`/`\rvert`, `\lVert`/`\rVert`, `\lfloor`/`\rfloor`,
+/// `\lceil`/`\rceil`, and `\langle`/`\rangle` into [`Node::Fenced`].
+/// [`EMPTY`] is the zero-width fence `\left.`/`\right.` resolve to.
+pub(crate) mod delimiter {
+    /// `\lvert` / `\rvert` — single vertical bar.
+    pub(crate) const VERT: (&str, &str) = ("|", "|");
+    /// `\lVert` / `\rVert` — double vertical bar (norm).
+    pub(crate) const DOUBLE_VERT: (&str, &str) = ("\u{2016}", "\u{2016}");
+    /// `\lfloor` / `\rfloor`.
+    pub(crate) const FLOOR: (&str, &str) = ("\u{230a}", "\u{230b}");
+    /// `\lceil` / `\rceil`.
+    pub(crate) const CEIL: (&str, &str) = ("\u{2308}", "\u{2309}");
+    /// `\langle` / `\rangle`.
+    pub(crate) const ANGLE: (&str, &str) = ("\u{27e8}", "\u{27e9}");
+    /// `\left.` / `\right.` — invisible, zero-width fence.
+    pub(crate) const EMPTY: &str = "";
+}
+
+/// The LaTeX matrix/array environment a [`Node::Table`] was parsed from,
+/// controlling the stretchy fences (if any) drawn around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixKind {
+    /// `matrix` — no delimiters.
+    Matrix,
+    /// `pmatrix` — parenthesis delimiters `(` `)`.
+    Paren,
+    /// `bmatrix` — square bracket delimiters `[` `]`.
+    Bracket,
+    /// `Bmatrix` — curly brace delimiters `{` `}`.
+    Brace,
+    /// `vmatrix` — single vertical bar delimiters.
+    Vert,
+    /// `Vmatrix` — double vertical bar delimiters.
+    DoubleVert,
+    /// `cases` — a single left brace, no closing delimiter.
+    Cases,
+    /// `array` — no delimiters; columns carry explicit alignment.
+    Array,
+}
+
+impl MatrixKind {
+    /// The `(open, close)` stretchy fence glyphs this kind wraps its table
+    /// in, or `None` if it draws no fences at all.
+    pub(crate) fn fences(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            MatrixKind::Matrix | MatrixKind::Array => None,
+            MatrixKind::Paren => Some(("(", ")")),
+            MatrixKind::Bracket => Some(("[", "]")),
+            MatrixKind::Brace => Some(("{", "}")),
+            MatrixKind::Vert => Some(("|", "|")),
+            MatrixKind::DoubleVert => Some(("\u{2016}", "\u{2016}")),
+            MatrixKind::Cases => Some(("{", "")),
+        }
+    }
+}
+
+/// Per-column alignment for a [`Node::Table`] cell, rendered as `mtd`'s
+/// `columnalign` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Align::Left => "left",
+            Align::Center => "center",
+            Align::Right => "right",
+        }
+    }
+}
 
 /// AST node
 #[derive(Debug, Clone, PartialEq)]
@@ -21,89 +122,39 @@ pub enum Node {
     Sqrt(Option<Box<Node>>, Box<Node>),
     Frac(Box<Node>, Box<Node>),
     Row(Vec<Node>),
+    /// A stretchy-fenced group, e.g. `(x)` or `\left(\right)`. `open`/`close`
+    /// are already-resolved Unicode delimiter glyphs, so this variant can
+    /// represent `\lvert x \rvert`, `\lfloor x \rfloor`, `\langle a, b
+    /// \rangle`, etc. just as well as plain parens, with either side `""`
+    /// for the invisible zero-width fence `\left.`/`\right.` produce. See
+    /// [`delimiter`] for the glyph pairs the parser uses for the named
+    /// commands.
     Fenced { open: &'static str, close: &'static str, content: Box<Node> },
     OtherOperator(&'static str),
     Text(String),
-    Matrix(Vec<Node>),
+    Table { rows: Vec<Vec<Node>>, kind: MatrixKind, col_align: Vec<Align> },
     Ampersand,
     NewLine,
     Slashed(Box<Node>),
     Undefined(String),
+    Styled { color: Option<String>, background: Option<String>, target: Box<Node> },
 }
 
+/// `Display` renders a node as presentation MathML using the default
+/// [`MathmlRenderer`]. To target a different backend, use [`Node::render`]
+/// with a custom [`super::renderer::Renderer`] implementation instead.
 impl fmt::Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Node::Number(number)  => write!(f, "<mn>{}</mn>", number),
-            Node::Letter(letter, var) => match var {
-                Variant::Italic => write!(f, "<mi>{}</mi>", letter),
-                var             => write!(f, r#"<mi mathvariant="{}">{}</mi>"#, var, letter),
-            },
-            Node::Operator(op) => write!(f, r#"<mo>{}</mo>"#, op),
-            Node::Function(fun, arg) => match arg {
-                Some(arg) => write!(f, "<mi>{}</mi><mo>&#x2061;</mo>{}", fun, arg),
-                None      => write!(f, "<mi>{}</mi>", fun),
-            },
-            Node::Space(space) => write!(f, r#"<mspace width="{}em"/>"#, space),
-            Node::Subscript(a, b) => write!(f, "<msub>{}{}</msub>", a, b),
-            Node::Superscript(a, b) => write!(f, "<msup>{}{}</msup>", a, b),
-            Node::SubSup{target, sub, sup} => write!(f, "<msubsup>{}{}{}</msubsup>", target, sub, sup),
-            Node::OverOp(op, acc, target) => write!(f, r#"<mover>{}<mo accent="{}">{}</mo></mover>"#, target, acc, op),
-            Node::UnderOp(op, acc, target) => write!(f, r#"<munder>{}<mo accent="{}">{}</mo></munder>"#, target, acc, op),
-            Node::Overset{over, target} => write!(f, r#"<mover>{}{}</mover>"#, target, over),
-            Node::Underset{under, target} => write!(f, r#"<munder>{}{}</munder>"#, target, under),
-            Node::Under(target, under) => write!(f, r#"<munder>{}{}</munder>"#, target, under),
-            Node::UnderOver{target, under, over} => write!(f, r#"<munderover>{}{}{}</munderover>"#, target, under, over),
-            Node::Sqrt(degree, content) => match degree {
-                Some(deg) => write!(f, "<mroot>{}{}</mroot>", content, deg),
-                None      => write!(f, "<msqrt>{}</msqrt>", content),
-            },
-            Node::Frac(num, denom) => write!(f, "<mfrac>{}{}</mfrac>", num, denom),
-            Node::Row(vec) => write!(f, "<mrow>{}</mrow>", 
-                vec.iter().map(|node| format!("{}", node)).collect::<String>()
-            ),
-            Node::Fenced{open, close, content} => {
-                write!(f, r#"<mrow><mo stretchy="true" form="prefix">{}</mo>{}<mo stretchy="true" form="postfix">{}</mo></mrow>"#, open, content, close)
-            },
-            Node::OtherOperator(op) => write!(f, "<mo>{}</mo>", op),
-            Node::Slashed(node) => match &**node {
-                Node::Letter(x, var) => write!(f, "<mi mathvariant=\"{}\">{}&#x0338;</mi>", var, x),
-                Node::Operator(x) => write!(f, "<mo>{}&#x0338;</mo>", x),
-                n => write!(f, "{}", n),
-            },
-            Node::Matrix(content) => {
-                let mut mathml = "<mtable><mtr><mtd>".to_owned();
-                for (i, node) in content.iter().enumerate() {
-                    match node {
-                        Node::NewLine => {
-                            mathml.push_str("</mtd></mtr>");
-                            if i < content.len() {
-                                mathml.push_str("<mtr><mtd>")
-                            }
-                        },
-                        Node::Ampersand => {
-                            mathml.push_str("</mtd>");
-                            if i < content.len() {
-                                mathml.push_str("<mtd>")
-                            }
-                        },
-                        node => { mathml = format!("{}{}", mathml, node); },
-                    }
-                }
-                mathml.push_str("</mtd></mtr></mtable>");
-                
-                write!(f, "{}", mathml)
-            },
-            Node::Text(text) => write!(f, "<mtext>{}</mtext>", text),
-            node => write!(f, "<mtext>[PARSE ERROR: {:?}]</mtext>", node),
-        }
+        let mut renderer = MathmlRenderer::new();
+        self.render(&mut renderer)?;
+        f.write_str(&renderer.into_inner())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::attribute::Variant;
-    use super::Node;
+    use super::{delimiter, MatrixKind, Node};
 
     #[test]
     fn node_display() {
@@ -113,9 +164,87 @@ mod tests {
             (Node::Letter('α', Variant::Italic), "<mi>α</mi>"),
             (Node::Letter('あ', Variant::Normal), r#"<mi mathvariant="normal">あ</mi>"#),
             (
-                Node::Row(vec![ Node::Operator('+'), Node::Operator('-') ]), 
+                Node::Row(vec![ Node::Operator('+'), Node::Operator('-') ]),
                 r"<mrow><mo>+</mo><mo>-</mo></mrow>"
             ),
+            (
+                Node::Text("if x & y".to_owned()),
+                "<mtext>if x &amp; y</mtext>"
+            ),
+            (
+                Node::Number("1 < 2".to_owned()),
+                "<mn>1 &lt; 2</mn>"
+            ),
+            (
+                Node::Operator('<'),
+                "<mo>&lt;</mo>"
+            ),
+            (
+                Node::Letter('<', Variant::Italic),
+                "<mi>&lt;</mi>"
+            ),
+            (
+                Node::Slashed(Box::new(Node::Operator('<'))),
+                "<mo>&lt;&#x0338;</mo>"
+            ),
+            (
+                Node::Styled {
+                    color: Some("red".to_owned()),
+                    background: None,
+                    target: Box::new(Node::Letter('x', Variant::Italic)),
+                },
+                r#"<mstyle mathcolor="red"><mi>x</mi></mstyle>"#
+            ),
+            (
+                Node::Styled {
+                    color: None,
+                    background: None,
+                    target: Box::new(Node::Letter('x', Variant::Italic)),
+                },
+                "<mstyle><mi>x</mi></mstyle>"
+            ),
+            (
+                Node::Table {
+                    rows: vec![
+                        vec![Node::Number("1".to_owned()), Node::Number("2".to_owned())],
+                        vec![Node::Number("3".to_owned()), Node::Number("4".to_owned())],
+                    ],
+                    kind: MatrixKind::Paren,
+                    col_align: vec![],
+                },
+                concat!(
+                    r#"<mrow><mo stretchy="true" form="prefix">(</mo>"#,
+                    "<mtable>",
+                    "<mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr>",
+                    "<mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr>",
+                    "</mtable>",
+                    r#"<mo stretchy="true" form="postfix">)</mo></mrow>"#,
+                )
+            ),
+            (
+                Node::Fenced {
+                    open: delimiter::FLOOR.0,
+                    close: delimiter::FLOOR.1,
+                    content: Box::new(Node::Letter('x', Variant::Italic)),
+                },
+                concat!(
+                    r#"<mrow><mo stretchy="true" form="prefix">⌊</mo>"#,
+                    "<mi>x</mi>",
+                    r#"<mo stretchy="true" form="postfix">⌋</mo></mrow>"#,
+                )
+            ),
+            (
+                Node::Fenced {
+                    open: delimiter::EMPTY,
+                    close: delimiter::ANGLE.1,
+                    content: Box::new(Node::Letter('x', Variant::Italic)),
+                },
+                concat!(
+                    r#"<mrow><mo stretchy="true" form="prefix"></mo>"#,
+                    "<mi>x</mi>",
+                    r#"<mo stretchy="true" form="postfix">⟩</mo></mrow>"#,
+                )
+            ),
         ];
         for (problem, answer) in problems.iter() {
             assert_eq!(&format!("{}", problem), answer);
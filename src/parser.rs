@@ -0,0 +1,407 @@
+//! A parser for the LaTeX constructs this tracked subset of the crate
+//! owns: stretchy delimiters (including the auto-sizing `\left…\right`
+//! form), `\color`/`\textcolor`/`\colorbox`, and matrix/array environments.
+//! It does not implement the crate's full expression grammar (functions,
+//! fractions, scripts, …) — that lives in the parser module that isn't
+//! part of this tracked tree — but [`parse`] is a real entry point that
+//! drives LaTeX text into the `Node` variants the other requests added,
+//! rather than only constructing them by hand in tests.
+
+use super::ast::{delimiter, Align, MatrixKind, Node};
+use super::attribute::Variant;
+
+/// Parse a single LaTeX atom: a run of ASCII digits (and `.`) as a number,
+/// a single letter as an italic `Letter`, or any other non-space character
+/// as an `Operator`. Returns the remaining input.
+fn parse_atom(input: &str) -> Option<(Node, &str)> {
+    let input = input.trim_start();
+    let first = input.chars().next()?;
+    if first.is_ascii_digit() {
+        let end = input
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(input.len());
+        return Some((Node::Number(input[..end].to_owned()), &input[end..]));
+    }
+    if first.is_alphabetic() {
+        return Some((Node::Letter(first, Variant::Italic), &input[first.len_utf8()..]));
+    }
+    Some((Node::Operator(first), &input[first.len_utf8()..]))
+}
+
+/// Parse a run of atoms, stopping at end of input or at a character in
+/// `stop`. A single atom is returned unwrapped; more than one is wrapped
+/// in a `Node::Row`, matching how the rest of the crate represents groups.
+fn parse_row_until(mut input: &str, stop: &[char]) -> Node {
+    let mut nodes = Vec::new();
+    loop {
+        input = input.trim_start();
+        match input.chars().next() {
+            None => break,
+            Some(c) if stop.contains(&c) => break,
+            _ => {}
+        }
+        match parse_atom(input) {
+            Some((node, rest)) => {
+                nodes.push(node);
+                input = rest;
+            }
+            None => break,
+        }
+    }
+    match nodes.len() {
+        1 => nodes.pop().unwrap(),
+        _ => Node::Row(nodes),
+    }
+}
+
+/// The LaTeX command pair and resolved glyphs for each named stretchy
+/// delimiter that isn't spelled with `\left`/`\right`.
+const NAMED_FENCES: &[(&str, &str, (&str, &str))] = &[
+    ("\\lvert", "\\rvert", delimiter::VERT),
+    ("\\lVert", "\\rVert", delimiter::DOUBLE_VERT),
+    ("\\lfloor", "\\rfloor", delimiter::FLOOR),
+    ("\\lceil", "\\rceil", delimiter::CEIL),
+    ("\\langle", "\\rangle", delimiter::ANGLE),
+];
+
+/// Resolve one `\left`/`\right` delimiter token (`(`, `)`, `[`, `]`, `\{`,
+/// `\}`, `|`, or `.` for the invisible zero-width fence) to its glyph.
+fn take_left_right_delimiter(input: &str) -> Option<(&'static str, &str)> {
+    let input = input.trim_start();
+    for (token, glyph) in [
+        ("\\{", "{"),
+        ("\\}", "}"),
+        ("(", "("),
+        (")", ")"),
+        ("[", "["),
+        ("]", "]"),
+        ("|", "|"),
+        (".", delimiter::EMPTY),
+    ] {
+        if let Some(rest) = input.strip_prefix(token) {
+            return Some((glyph, rest));
+        }
+    }
+    None
+}
+
+/// Parse `\lvert x \rvert`-style named fences and auto-sizing
+/// `\left…\right` pairs into a [`Node::Fenced`].
+pub(crate) fn try_parse_fenced(input: &str) -> Option<(Node, &str)> {
+    let input = input.trim_start();
+    for entry in NAMED_FENCES {
+        let (open_cmd, close_cmd, (open, close)) = *entry;
+        if let Some(rest) = input.strip_prefix(open_cmd) {
+            let end = rest.find(close_cmd)?;
+            let content = parse_row_until(&rest[..end], &[]);
+            let rest = &rest[end + close_cmd.len()..];
+            return Some((
+                Node::Fenced { open, close, content: Box::new(content) },
+                rest,
+            ));
+        }
+    }
+    if let Some(rest) = input.strip_prefix("\\left") {
+        let (open, rest) = take_left_right_delimiter(rest)?;
+        let right_idx = rest.find("\\right")?;
+        let content = parse_row_until(&rest[..right_idx], &[]);
+        let rest = &rest[right_idx + "\\right".len()..];
+        let (close, rest) = take_left_right_delimiter(rest)?;
+        return Some((Node::Fenced { open, close, content: Box::new(content) }, rest));
+    }
+    None
+}
+
+/// Take a single `{…}`-delimited, brace-balanced group from the start of
+/// `input`, returning its inner text and the remaining input.
+fn take_group(input: &str) -> Option<(&str, &str)> {
+    let input = input.trim_start();
+    let mut chars = input.char_indices();
+    match chars.next() {
+        Some((_, '{')) => {}
+        _ => return None,
+    }
+    let mut depth = 1;
+    for (i, c) in chars {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((&input[1..i], &input[i + 1..]));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The `\color`/`\textcolor`/`\colorbox` commands, and whether each sets
+/// `mathbackground` (as opposed to `mathcolor`) on the `Node::Styled` it
+/// produces. `\colorbox` must be checked before `\color`, since `\color`
+/// is itself a literal prefix of `\colorbox`.
+const COLOR_COMMANDS: &[(&str, bool)] = &[("\\colorbox", true), ("\\textcolor", false), ("\\color", false)];
+
+/// Parse `\color{red}{x}`, `\textcolor{#00f}{x}`, or `\colorbox{yellow}{x}`
+/// into a [`Node::Styled`]. Named colors and `#rrggbb`/`#rgb` hex both pass
+/// through unchanged, as the request asked for.
+pub(crate) fn try_parse_styled(input: &str) -> Option<(Node, &str)> {
+    let input = input.trim_start();
+    let (is_background, rest) = COLOR_COMMANDS.iter().find_map(|entry| {
+        let (cmd, is_background) = *entry;
+        input.strip_prefix(cmd).map(|rest| (is_background, rest))
+    })?;
+    let (color, rest) = take_group(rest)?;
+    let (target_src, rest) = take_group(rest)?;
+    let target = Box::new(parse_row_until(target_src, &[]));
+    let color = color.trim().to_owned();
+    let node = if is_background {
+        Node::Styled { color: None, background: Some(color), target }
+    } else {
+        Node::Styled { color: Some(color), background: None, target }
+    };
+    Some((node, rest))
+}
+
+/// The non-`array` matrix environments, mapped to the `MatrixKind` they
+/// produce.
+const MATRIX_ENVIRONMENTS: &[(&str, MatrixKind)] = &[
+    ("matrix", MatrixKind::Matrix),
+    ("pmatrix", MatrixKind::Paren),
+    ("bmatrix", MatrixKind::Bracket),
+    ("Bmatrix", MatrixKind::Brace),
+    ("vmatrix", MatrixKind::Vert),
+    ("Vmatrix", MatrixKind::DoubleVert),
+    ("cases", MatrixKind::Cases),
+];
+
+/// Parse an `array` column-spec such as `"clr"` or `"c|c|c"` (pipes are
+/// alignment-only separators in this tracked subset, so they're skipped)
+/// into per-column [`Align`]s.
+fn parse_col_align(col_spec: &str) -> Vec<Align> {
+    col_spec
+        .chars()
+        .filter_map(|c| match c {
+            'l' => Some(Align::Left),
+            'c' => Some(Align::Center),
+            'r' => Some(Align::Right),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Split a matrix/array environment body into rows (on `\\`) and cells
+/// (on `&`), parsing each cell's content.
+fn parse_table_body(kind: MatrixKind, col_align: Vec<Align>, body: &str) -> Node {
+    let rows = body
+        .split("\\\\")
+        .map(|row| row.trim())
+        .filter(|row| !row.is_empty())
+        .map(|row| row.split('&').map(|cell| parse_row_until(cell, &[])).collect())
+        .collect();
+    Node::Table { rows, kind, col_align }
+}
+
+/// Parse `\begin{pmatrix}…\end{pmatrix}`-style matrix environments and
+/// `\begin{array}{cols}…\end{array}`, populating `col_align` from the
+/// environment's column spec for `array` (`cases` and the other matrix
+/// kinds have no column spec and get an empty `col_align`).
+pub(crate) fn try_parse_table(input: &str) -> Option<(Node, &str)> {
+    let input = input.trim_start();
+    let rest = input.strip_prefix("\\begin")?;
+    let (env, rest) = take_group(rest)?;
+    let (kind, col_align, rest) = if env == "array" {
+        let (col_spec, rest) = take_group(rest)?;
+        (MatrixKind::Array, parse_col_align(col_spec), rest)
+    } else {
+        let kind = MATRIX_ENVIRONMENTS.iter().find(|entry| entry.0 == env)?.1;
+        (kind, Vec::new(), rest)
+    };
+    let end_tag = format!("\\end{{{}}}", env);
+    let end_idx = rest.find(&end_tag)?;
+    let body = &rest[..end_idx];
+    let rest = &rest[end_idx + end_tag.len()..];
+    Some((parse_table_body(kind, col_align, body), rest))
+}
+
+/// Parse a single fenced, styled, or matrix-like construct from the start
+/// of `input`, returning the remaining input. This is the entry point a
+/// full expression parser would delegate to for the constructs this
+/// tracked subset owns.
+pub fn parse(input: &str) -> Option<(Node, &str)> {
+    try_parse_table(input)
+        .or_else(|| try_parse_fenced(input))
+        .or_else(|| try_parse_styled(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ast::{Align, MatrixKind, Node};
+    use super::super::attribute::Variant;
+    use super::parse;
+
+    #[test]
+    fn parse_named_fences() {
+        let problems = vec![
+            (
+                "\\lvert x \\rvert",
+                Node::Fenced {
+                    open: "|",
+                    close: "|",
+                    content: Box::new(Node::Letter('x', Variant::Italic)),
+                },
+            ),
+            (
+                "\\lVert v \\rVert",
+                Node::Fenced {
+                    open: "\u{2016}",
+                    close: "\u{2016}",
+                    content: Box::new(Node::Letter('v', Variant::Italic)),
+                },
+            ),
+            (
+                "\\lfloor x \\rfloor",
+                Node::Fenced {
+                    open: "\u{230a}",
+                    close: "\u{230b}",
+                    content: Box::new(Node::Letter('x', Variant::Italic)),
+                },
+            ),
+            (
+                "\\lceil x \\rceil",
+                Node::Fenced {
+                    open: "\u{2308}",
+                    close: "\u{2309}",
+                    content: Box::new(Node::Letter('x', Variant::Italic)),
+                },
+            ),
+            (
+                "\\langle a \\rangle",
+                Node::Fenced {
+                    open: "\u{27e8}",
+                    close: "\u{27e9}",
+                    content: Box::new(Node::Letter('a', Variant::Italic)),
+                },
+            ),
+        ];
+        for (input, expected) in problems {
+            let (node, rest) = parse(input).unwrap();
+            assert_eq!(node, expected);
+            assert_eq!(rest, "");
+        }
+    }
+
+    #[test]
+    fn parse_left_right_auto_sizing() {
+        let (node, rest) = parse("\\left( x \\right)").unwrap();
+        assert_eq!(
+            node,
+            Node::Fenced {
+                open: "(",
+                close: ")",
+                content: Box::new(Node::Letter('x', Variant::Italic)),
+            }
+        );
+        assert_eq!(rest, "");
+    }
+
+    #[test]
+    fn parse_color_commands() {
+        let problems = vec![
+            (
+                "\\color{red}{x}",
+                Node::Styled {
+                    color: Some("red".to_owned()),
+                    background: None,
+                    target: Box::new(Node::Letter('x', Variant::Italic)),
+                },
+            ),
+            (
+                "\\textcolor{#00f}{x}",
+                Node::Styled {
+                    color: Some("#00f".to_owned()),
+                    background: None,
+                    target: Box::new(Node::Letter('x', Variant::Italic)),
+                },
+            ),
+            (
+                "\\colorbox{yellow}{x}",
+                Node::Styled {
+                    color: None,
+                    background: Some("yellow".to_owned()),
+                    target: Box::new(Node::Letter('x', Variant::Italic)),
+                },
+            ),
+        ];
+        for (input, expected) in problems {
+            let (node, rest) = parse(input).unwrap();
+            assert_eq!(node, expected);
+            assert_eq!(rest, "");
+        }
+    }
+
+    #[test]
+    fn parse_pmatrix_environment() {
+        let (node, rest) = parse("\\begin{pmatrix}1 & 2 \\\\ 3 & 4\\end{pmatrix}").unwrap();
+        assert_eq!(
+            node,
+            Node::Table {
+                rows: vec![
+                    vec![Node::Number("1".to_owned()), Node::Number("2".to_owned())],
+                    vec![Node::Number("3".to_owned()), Node::Number("4".to_owned())],
+                ],
+                kind: MatrixKind::Paren,
+                col_align: vec![],
+            }
+        );
+        assert_eq!(rest, "");
+    }
+
+    #[test]
+    fn parse_array_environment_populates_col_align() {
+        let (node, rest) = parse("\\begin{array}{lcr}x & y & z\\end{array}").unwrap();
+        assert_eq!(
+            node,
+            Node::Table {
+                rows: vec![vec![
+                    Node::Letter('x', Variant::Italic),
+                    Node::Letter('y', Variant::Italic),
+                    Node::Letter('z', Variant::Italic),
+                ]],
+                kind: MatrixKind::Array,
+                col_align: vec![Align::Left, Align::Center, Align::Right],
+            }
+        );
+        assert_eq!(rest, "");
+    }
+
+    #[test]
+    fn parse_cases_environment() {
+        let (node, _) = parse("\\begin{cases}1 & x \\\\ 0 & y\\end{cases}").unwrap();
+        assert_eq!(
+            node,
+            Node::Table {
+                rows: vec![
+                    vec![Node::Number("1".to_owned()), Node::Letter('x', Variant::Italic)],
+                    vec![Node::Number("0".to_owned()), Node::Letter('y', Variant::Italic)],
+                ],
+                kind: MatrixKind::Cases,
+                col_align: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn parse_left_dot_is_zero_width_on_the_left() {
+        let (node, _) = parse("\\left. x \\right|").unwrap();
+        assert_eq!(
+            node,
+            Node::Fenced {
+                open: "",
+                close: "|",
+                content: Box::new(Node::Letter('x', Variant::Italic)),
+            }
+        );
+    }
+}
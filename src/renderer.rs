@@ -0,0 +1,323 @@
+use std::fmt;
+use super::ast::{escape, Align, MatrixKind, Node};
+use super::attribute::{Accent, Variant};
+
+/// A pluggable backend for turning a [`Node`] tree into markup.
+///
+/// Apart from the required [`Renderer::write_str`] and the dispatching
+/// [`Renderer::render`], every method corresponds to one structural
+/// construct in the AST and carries a default implementation that produces
+/// the crate's usual presentation MathML. Override individual methods to customize a single
+/// construct — for example to add `class="…"` hooks for MathJax/KaTeX CSS,
+/// emit self-closing XHTML, wrap output in `display="block"`, or dump a
+/// debug tree — without forking the rest of the render walk.
+///
+/// [`MathmlRenderer`] is the default implementation and backs `Node`'s
+/// `Display` impl.
+pub trait Renderer {
+    /// Write a raw string straight to the output. Every other method is
+    /// built on top of this one primitive.
+    fn write_str(&mut self, s: &str) -> fmt::Result;
+
+    /// Render `node`, dispatching to the method for its variant.
+    fn render(&mut self, node: &Node) -> fmt::Result {
+        node.render(self)
+    }
+
+    fn number(&mut self, number: &str) -> fmt::Result {
+        self.write_str(&format!("<mn>{}</mn>", escape(number)))
+    }
+
+    fn letter(&mut self, letter: char, variant: &Variant) -> fmt::Result {
+        match variant {
+            Variant::Italic => self.write_str(&format!("<mi>{}</mi>", escape(&letter.to_string()))),
+            variant => self.write_str(&format!(r#"<mi mathvariant="{}">{}</mi>"#, variant, escape(&letter.to_string()))),
+        }
+    }
+
+    fn operator(&mut self, op: char) -> fmt::Result {
+        self.write_str(&format!(r#"<mo>{}</mo>"#, escape(&op.to_string())))
+    }
+
+    fn function(&mut self, name: &str, arg: Option<&Node>) -> fmt::Result {
+        self.write_str(&format!("<mi>{}</mi>", name))?;
+        match arg {
+            Some(arg) => {
+                self.write_str("<mo>&#x2061;</mo>")?;
+                self.render(arg)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn space(&mut self, width: f32) -> fmt::Result {
+        self.write_str(&format!(r#"<mspace width="{}em"/>"#, width))
+    }
+
+    fn subscript(&mut self, base: &Node, sub: &Node) -> fmt::Result {
+        self.write_str("<msub>")?;
+        self.render(base)?;
+        self.render(sub)?;
+        self.write_str("</msub>")
+    }
+
+    fn superscript(&mut self, base: &Node, sup: &Node) -> fmt::Result {
+        self.write_str("<msup>")?;
+        self.render(base)?;
+        self.render(sup)?;
+        self.write_str("</msup>")
+    }
+
+    fn subsup(&mut self, target: &Node, sub: &Node, sup: &Node) -> fmt::Result {
+        self.write_str("<msubsup>")?;
+        self.render(target)?;
+        self.render(sub)?;
+        self.render(sup)?;
+        self.write_str("</msubsup>")
+    }
+
+    fn over_op(&mut self, op: char, accent: &Accent, target: &Node) -> fmt::Result {
+        self.write_str("<mover>")?;
+        self.render(target)?;
+        self.write_str(&format!(r#"<mo accent="{}">{}</mo>"#, accent, escape(&op.to_string())))?;
+        self.write_str("</mover>")
+    }
+
+    fn under_op(&mut self, op: char, accent: &Accent, target: &Node) -> fmt::Result {
+        self.write_str("<munder>")?;
+        self.render(target)?;
+        self.write_str(&format!(r#"<mo accent="{}">{}</mo>"#, accent, escape(&op.to_string())))?;
+        self.write_str("</munder>")
+    }
+
+    fn overset(&mut self, over: &Node, target: &Node) -> fmt::Result {
+        self.write_str("<mover>")?;
+        self.render(target)?;
+        self.render(over)?;
+        self.write_str("</mover>")
+    }
+
+    fn underset(&mut self, under: &Node, target: &Node) -> fmt::Result {
+        self.write_str("<munder>")?;
+        self.render(target)?;
+        self.render(under)?;
+        self.write_str("</munder>")
+    }
+
+    fn under(&mut self, target: &Node, under: &Node) -> fmt::Result {
+        self.write_str("<munder>")?;
+        self.render(target)?;
+        self.render(under)?;
+        self.write_str("</munder>")
+    }
+
+    fn under_over(&mut self, target: &Node, under: &Node, over: &Node) -> fmt::Result {
+        self.write_str("<munderover>")?;
+        self.render(target)?;
+        self.render(under)?;
+        self.render(over)?;
+        self.write_str("</munderover>")
+    }
+
+    fn sqrt(&mut self, degree: Option<&Node>, content: &Node) -> fmt::Result {
+        match degree {
+            Some(deg) => {
+                self.write_str("<mroot>")?;
+                self.render(content)?;
+                self.render(deg)?;
+                self.write_str("</mroot>")
+            }
+            None => {
+                self.write_str("<msqrt>")?;
+                self.render(content)?;
+                self.write_str("</msqrt>")
+            }
+        }
+    }
+
+    fn frac(&mut self, num: &Node, denom: &Node) -> fmt::Result {
+        self.write_str("<mfrac>")?;
+        self.render(num)?;
+        self.render(denom)?;
+        self.write_str("</mfrac>")
+    }
+
+    fn row(&mut self, children: &[Node]) -> fmt::Result {
+        self.write_str("<mrow>")?;
+        for child in children {
+            self.render(child)?;
+        }
+        self.write_str("</mrow>")
+    }
+
+    fn fenced(&mut self, open: &str, close: &str, content: &Node) -> fmt::Result {
+        self.write_str(r#"<mrow><mo stretchy="true" form="prefix">"#)?;
+        self.write_str(open)?;
+        self.write_str("</mo>")?;
+        self.render(content)?;
+        self.write_str(r#"<mo stretchy="true" form="postfix">"#)?;
+        self.write_str(close)?;
+        self.write_str("</mo></mrow>")
+    }
+
+    fn other_operator(&mut self, op: &str) -> fmt::Result {
+        self.write_str(&format!("<mo>{}</mo>", escape(op)))
+    }
+
+    fn text(&mut self, text: &str) -> fmt::Result {
+        self.write_str(&format!("<mtext>{}</mtext>", escape(text)))
+    }
+
+    fn table(&mut self, rows: &[Vec<Node>], kind: MatrixKind, col_align: &[Align]) -> fmt::Result {
+        let fences = kind.fences();
+        if fences.is_some() {
+            self.write_str("<mrow>")?;
+        }
+        if let Some((open, _)) = fences {
+            if !open.is_empty() {
+                self.write_str(&format!(r#"<mo stretchy="true" form="prefix">{}</mo>"#, open))?;
+            }
+        }
+        self.write_str("<mtable>")?;
+        for row in rows {
+            self.write_str("<mtr>")?;
+            for (i, cell) in row.iter().enumerate() {
+                match col_align.get(i) {
+                    Some(align) => self.write_str(&format!(r#"<mtd columnalign="{}">"#, align.as_str()))?,
+                    None => self.write_str("<mtd>")?,
+                }
+                self.render(cell)?;
+                self.write_str("</mtd>")?;
+            }
+            self.write_str("</mtr>")?;
+        }
+        self.write_str("</mtable>")?;
+        if let Some((_, close)) = fences {
+            if !close.is_empty() {
+                self.write_str(&format!(r#"<mo stretchy="true" form="postfix">{}</mo>"#, close))?;
+            }
+        }
+        if fences.is_some() {
+            self.write_str("</mrow>")?;
+        }
+        Ok(())
+    }
+
+    fn slashed(&mut self, node: &Node) -> fmt::Result {
+        match node {
+            Node::Letter(x, var) => self.write_str(&format!("<mi mathvariant=\"{}\">{}&#x0338;</mi>", var, escape(&x.to_string()))),
+            Node::Operator(x) => self.write_str(&format!("<mo>{}&#x0338;</mo>", escape(&x.to_string()))),
+            n => self.render(n),
+        }
+    }
+
+    /// `Ampersand` and `NewLine` are leftover sentinels from the legacy
+    /// matrix representation and carry no meaning on their own; encountered
+    /// standalone they render the same parse-error marker the legacy
+    /// `Display` impl used for any unhandled variant.
+    fn ampersand(&mut self) -> fmt::Result {
+        self.write_str(&format!("<mtext>[PARSE ERROR: {:?}]</mtext>", Node::Ampersand))
+    }
+
+    fn new_line(&mut self) -> fmt::Result {
+        self.write_str(&format!("<mtext>[PARSE ERROR: {:?}]</mtext>", Node::NewLine))
+    }
+
+    fn undefined(&mut self, name: &str) -> fmt::Result {
+        self.write_str(&format!("<mtext>[PARSE ERROR: {:?}]</mtext>", Node::Undefined(name.to_owned())))
+    }
+
+    fn styled(&mut self, color: Option<&str>, background: Option<&str>, target: &Node) -> fmt::Result {
+        self.write_str("<mstyle")?;
+        if let Some(color) = color {
+            self.write_str(&format!(r#" mathcolor="{}""#, escape(color)))?;
+        }
+        if let Some(background) = background {
+            self.write_str(&format!(r#" mathbackground="{}""#, escape(background)))?;
+        }
+        self.write_str(">")?;
+        self.render(target)?;
+        self.write_str("</mstyle>")
+    }
+}
+
+impl Node {
+    /// Walk this node (and its children) through `renderer`, driving one
+    /// [`Renderer`] method per structural construct.
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &mut R) -> fmt::Result {
+        match self {
+            Node::Number(number) => renderer.number(number),
+            Node::Letter(letter, variant) => renderer.letter(*letter, variant),
+            Node::Operator(op) => renderer.operator(*op),
+            Node::Function(name, arg) => renderer.function(name, arg.as_deref()),
+            Node::Space(width) => renderer.space(*width),
+            Node::Subscript(base, sub) => renderer.subscript(base, sub),
+            Node::Superscript(base, sup) => renderer.superscript(base, sup),
+            Node::SubSup { target, sub, sup } => renderer.subsup(target, sub, sup),
+            Node::OverOp(op, accent, target) => renderer.over_op(*op, accent, target),
+            Node::UnderOp(op, accent, target) => renderer.under_op(*op, accent, target),
+            Node::Overset { over, target } => renderer.overset(over, target),
+            Node::Underset { under, target } => renderer.underset(under, target),
+            Node::Under(target, under) => renderer.under(target, under),
+            Node::UnderOver { target, under, over } => renderer.under_over(target, under, over),
+            Node::Sqrt(degree, content) => renderer.sqrt(degree.as_deref(), content),
+            Node::Frac(num, denom) => renderer.frac(num, denom),
+            Node::Row(children) => renderer.row(children),
+            Node::Fenced { open, close, content } => renderer.fenced(open, close, content),
+            Node::OtherOperator(op) => renderer.other_operator(op),
+            Node::Text(text) => renderer.text(text),
+            Node::Table { rows, kind, col_align } => renderer.table(rows, *kind, col_align),
+            Node::Ampersand => renderer.ampersand(),
+            Node::NewLine => renderer.new_line(),
+            Node::Slashed(node) => renderer.slashed(node),
+            Node::Undefined(name) => renderer.undefined(name),
+            Node::Styled { color, background, target } => {
+                renderer.styled(color.as_deref(), background.as_deref(), target)
+            }
+        }
+    }
+}
+
+/// The default [`Renderer`]: presentation MathML, matching the behavior of
+/// `Node`'s `Display` impl.
+#[derive(Debug, Default)]
+pub struct MathmlRenderer {
+    out: String,
+}
+
+impl MathmlRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Consume the renderer, returning the markup written so far.
+    pub fn into_inner(self) -> String {
+        self.out
+    }
+}
+
+impl Renderer for MathmlRenderer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.push_str(s);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::attribute::Variant;
+    use super::super::ast::Node;
+    use super::MathmlRenderer;
+
+    #[test]
+    fn mathml_renderer_matches_display() {
+        let node = Node::Row(vec![
+            Node::Letter('x', Variant::Italic),
+            Node::Operator('+'),
+            Node::Number("1".to_owned()),
+        ]);
+        let mut renderer = MathmlRenderer::new();
+        node.render(&mut renderer).unwrap();
+        assert_eq!(renderer.into_inner(), format!("{}", node));
+    }
+}